@@ -37,11 +37,14 @@ extern crate chrono;
 extern crate libc;
 #[macro_use]
 extern crate nix;
+#[cfg(feature = "rtcc")]
+extern crate rtcc;
 
-use chrono::{Datelike, Timelike};
+use chrono::{Datelike, TimeZone, Timelike};
 
-use libc::c_int;
+use libc::{c_int, c_long, c_ulong};
 use std::{fs, io, path};
+use std::io::Read;
 use std::os::unix::io::AsRawFd;
 
 /// Basic epoch for dates.
@@ -52,18 +55,48 @@ use std::os::unix::io::AsRawFd;
 pub const YEAR_EPOCH: i32 = 1900;
 
 mod ffi {
-    use super::RtcTime;
+    use super::{RtcPllInfo, RtcTime, RtcWkAlarm};
+    use libc::{c_int, c_ulong};
 
     // ioctls, stolen from linux/rtc.h
     const RTC_IOC_MAGIC: u8 = b'p';
     ioctl_read!(rtc_rd_time, RTC_IOC_MAGIC, 0x09, RtcTime);
     ioctl_write_ptr!(rtc_set_time, RTC_IOC_MAGIC, 0x0a, RtcTime);
+
+    ioctl_write_ptr!(rtc_alm_set, RTC_IOC_MAGIC, 0x07, RtcTime);
+    ioctl_read!(rtc_alm_read, RTC_IOC_MAGIC, 0x08, RtcTime);
+
+    ioctl_write_ptr!(rtc_wkalm_set, RTC_IOC_MAGIC, 0x0f, RtcWkAlarm);
+    ioctl_read!(rtc_wkalm_rd, RTC_IOC_MAGIC, 0x10, RtcWkAlarm);
+
+    ioctl_none!(rtc_aie_on, RTC_IOC_MAGIC, 0x01);
+    ioctl_none!(rtc_aie_off, RTC_IOC_MAGIC, 0x02);
+
+    ioctl_none!(rtc_uie_on, RTC_IOC_MAGIC, 0x03);
+    ioctl_none!(rtc_uie_off, RTC_IOC_MAGIC, 0x04);
+
+    ioctl_none!(rtc_pie_on, RTC_IOC_MAGIC, 0x05);
+    ioctl_none!(rtc_pie_off, RTC_IOC_MAGIC, 0x06);
+
+    ioctl_write_int!(rtc_irqp_set, RTC_IOC_MAGIC, 0x0c);
+    ioctl_read!(rtc_irqp_read, RTC_IOC_MAGIC, 0x0b, c_ulong);
+
+    ioctl_read!(rtc_epoch_read, RTC_IOC_MAGIC, 0x0d, c_ulong);
+    ioctl_write_int!(rtc_epoch_set, RTC_IOC_MAGIC, 0x0e);
+
+    ioctl_read!(rtc_pll_get, RTC_IOC_MAGIC, 0x11, RtcPllInfo);
+    ioctl_write_ptr!(rtc_pll_set, RTC_IOC_MAGIC, 0x12, RtcPllInfo);
+
+    ioctl_read!(rtc_vl_read, RTC_IOC_MAGIC, 0x13, c_int);
 }
 
 /// Linux `struct rtc_time` wrapper
 ///
 /// This structure is slightly shorter than other commonly used `struct tm*`.
-/// It is assumed that the Rtc is kept at UTC.
+/// It carries no timezone information of its own; whether it is in UTC or
+/// local time depends on how the hardware was configured. See
+/// [`HwClockDev::open_with_mode`] and [`ClockMode`] for how this crate
+/// models that.
 ///
 /// Note that the resolution of the time struct is only seconds.
 ///
@@ -92,16 +125,270 @@ pub struct RtcTime {
     pub tm_isdst: c_int,
 }
 
+/// Linux `struct rtc_wkalm` wrapper
+///
+/// The wakeup-alarm variant of `RtcTime` used by `RTC_WKALM_SET`/`RTC_WKALM_RD`.
+/// Unlike the plain `RTC_ALM_SET`/`RTC_ALM_READ` pair, it also carries whether
+/// the alarm is enabled and whether it has already fired.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct RtcWkAlarm {
+    /// Whether the alarm interrupt is enabled
+    pub enabled: u8,
+    /// Whether the alarm is pending (has fired and not yet been acknowledged)
+    pub pending: u8,
+    /// The time the alarm is set to go off at
+    pub time: RtcTime,
+}
+
+/// Alarm/update interrupt occurrence
+///
+/// Returned by [`HwClockDev::wait_for_alarm`] after a blocking `read()` of the
+/// RTC device, decoded from the `c_ulong` the kernel hands back.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct AlarmEvent {
+    /// Bitmask of `RTC_IRQF`/`RTC_AF`/... flags that were set, found in the
+    /// lower byte of the value returned by the kernel.
+    pub flags: u8,
+    /// Number of interrupts that occurred, found in the upper bits of the
+    /// value returned by the kernel.
+    pub count: c_ulong,
+}
+
+/// `RTC_IRQF` flag: an interrupt of any of the below kinds occurred.
+pub const RTC_IRQF: u8 = 0x80;
+/// `RTC_AF` flag: an alarm interrupt occurred.
+pub const RTC_AF: u8 = 0x20;
+/// `RTC_UF` flag: an update interrupt occurred.
+pub const RTC_UF: u8 = 0x10;
+/// `RTC_PF` flag: a periodic interrupt occurred.
+pub const RTC_PF: u8 = 0x40;
+
+/// Linux `struct rtc_pll_info` wrapper
+///
+/// Describes the oscillator's phase-locked loop correction, for hardware
+/// that supports trimming out a known, constant drift.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct RtcPllInfo {
+    /// PLL correction enabled/disabled
+    pub ctrl: c_int,
+    /// Current value of the PLL correction
+    pub value: c_int,
+    /// Maximum allowed PLL correction value
+    pub max: c_int,
+    /// Minimum allowed PLL correction value
+    pub min: c_int,
+    /// Positive PLL correction multiplier
+    pub posmult: c_int,
+    /// Negative PLL correction multiplier
+    pub negmult: c_int,
+    /// PLL input clock frequency, in Hz
+    pub clock: c_long,
+}
+
+impl RtcTime {
+    /// Checked conversion to `chrono::NaiveDateTime`
+    ///
+    /// Returns `None` instead of panicking if any of the fields are out of
+    /// range, which can happen when reading from an uninitialized or
+    /// misbehaving RTC (e.g. a bogus `tm_mon` of 13).
+    pub fn to_naive_opt(&self) -> Option<chrono::NaiveDateTime> {
+        let d = chrono::NaiveDate::from_ymd_opt(
+            self.tm_year.checked_add(YEAR_EPOCH)?,
+            self.tm_mon.checked_add(1)? as u32,
+            self.tm_mday as u32,
+        )?;
+        let t = chrono::NaiveTime::from_hms_opt(
+            self.tm_hour as u32,
+            self.tm_min as u32,
+            self.tm_sec as u32,
+        )?;
+
+        Some(chrono::NaiveDateTime::new(d, t))
+    }
+}
+
+impl Timelike for RtcTime {
+    #[inline]
+    fn second(&self) -> u32 {
+        self.tm_sec as u32
+    }
+
+    #[inline]
+    fn minute(&self) -> u32 {
+        self.tm_min as u32
+    }
+
+    #[inline]
+    fn hour(&self) -> u32 {
+        self.tm_hour as u32
+    }
+
+    #[inline]
+    fn nanosecond(&self) -> u32 {
+        0
+    }
+
+    #[inline]
+    fn with_hour(&self, hour: u32) -> Option<Self> {
+        if hour < 24 {
+            Some(RtcTime {
+                tm_hour: hour as i32,
+                ..*self
+            })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn with_minute(&self, minute: u32) -> Option<Self> {
+        if minute < 60 {
+            Some(RtcTime {
+                tm_min: minute as i32,
+                ..*self
+            })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn with_second(&self, second: u32) -> Option<Self> {
+        if second < 60 {
+            Some(RtcTime {
+                tm_sec: second as i32,
+                ..*self
+            })
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    fn with_nanosecond(&self, _: u32) -> Option<Self> {
+        Some(*self)
+    }
+}
+
+impl Datelike for RtcTime {
+    #[inline]
+    fn year(&self) -> i32 {
+        self.tm_year + YEAR_EPOCH
+    }
+
+    #[inline]
+    fn month(&self) -> u32 {
+        (self.tm_mon + 1) as u32
+    }
+
+    #[inline]
+    fn month0(&self) -> u32 {
+        self.tm_mon as u32
+    }
+
+    #[inline]
+    fn day(&self) -> u32 {
+        self.tm_mday as u32
+    }
+
+    #[inline]
+    fn day0(&self) -> u32 {
+        self.to_naive_opt()
+            .expect("RtcTime holds an invalid date")
+            .day0()
+    }
+
+    #[inline]
+    fn ordinal(&self) -> u32 {
+        self.to_naive_opt()
+            .expect("RtcTime holds an invalid date")
+            .ordinal()
+    }
+
+    #[inline]
+    fn ordinal0(&self) -> u32 {
+        self.ordinal() - 1
+    }
+
+    #[inline]
+    fn weekday(&self) -> chrono::Weekday {
+        self.to_naive_opt()
+            .expect("RtcTime holds an invalid date")
+            .weekday()
+    }
+
+    #[inline]
+    fn iso_week(&self) -> chrono::IsoWeek {
+        self.to_naive_opt()
+            .expect("RtcTime holds an invalid date")
+            .iso_week()
+    }
+
+    #[inline]
+    fn with_year(&self, year: i32) -> Option<Self> {
+        let candidate = RtcTime {
+            tm_year: year - YEAR_EPOCH,
+            ..*self
+        };
+        candidate.to_naive_opt().map(|_| candidate)
+    }
+
+    #[inline]
+    fn with_month(&self, month: u32) -> Option<Self> {
+        if month < 1 || month > 12 {
+            return None;
+        }
+
+        let candidate = RtcTime {
+            tm_mon: (month - 1) as i32,
+            ..*self
+        };
+        candidate.to_naive_opt().map(|_| candidate)
+    }
+
+    #[inline]
+    fn with_month0(&self, month0: u32) -> Option<Self> {
+        self.with_month(month0 + 1)
+    }
+
+    #[inline]
+    fn with_day(&self, day: u32) -> Option<Self> {
+        let candidate = RtcTime {
+            tm_mday: day as i32,
+            ..*self
+        };
+        candidate.to_naive_opt().map(|_| candidate)
+    }
+
+    #[inline]
+    fn with_day0(&self, day0: u32) -> Option<Self> {
+        self.with_day(day0 + 1)
+    }
+
+    #[inline]
+    fn with_ordinal(&self, ordinal: u32) -> Option<Self> {
+        let naive = self.to_naive_opt()?;
+        let date = chrono::NaiveDate::from_yo_opt(naive.year(), ordinal)?;
+
+        Some(RtcTime {
+            tm_mon: date.month0() as i32,
+            tm_mday: date.day() as i32,
+            ..*self
+        })
+    }
+
+    #[inline]
+    fn with_ordinal0(&self, ordinal0: u32) -> Option<Self> {
+        self.with_ordinal(ordinal0 + 1)
+    }
+}
+
 impl From<RtcTime> for chrono::NaiveDateTime {
     fn from(rtc: RtcTime) -> chrono::NaiveDateTime {
-        let d = chrono::NaiveDate::from_ymd(
-            rtc.tm_year as i32 + YEAR_EPOCH,
-            (rtc.tm_mon + 1) as u32,
-            rtc.tm_mday as u32,
-        );
-        let t =
-            chrono::NaiveTime::from_hms(rtc.tm_hour as u32, rtc.tm_min as u32, rtc.tm_sec as u32);
-        chrono::NaiveDateTime::new(d, t)
+        rtc.to_naive_opt()
+            .expect("RtcTime holds an invalid date/time and cannot be converted")
     }
 }
 
@@ -166,6 +453,166 @@ mod tests {
             concat!("Alignment of ", stringify!(RtcTime))
         );
     }
+
+    #[test]
+    fn invalid_rtc_time_does_not_convert() {
+        let rtc = RtcTime {
+            tm_mon: 12, // months are 0-11, so 12 is out of range
+            tm_mday: 19,
+            tm_year: 118,
+            ..RtcTime::default()
+        };
+
+        assert_eq!(None, rtc.to_naive_opt());
+    }
+
+    /// A toy US-Eastern-like timezone with a single hand-picked DST
+    /// transition pair, just precise enough to exercise the fold/gap
+    /// handling in `naive_to_datetime_tz` without depending on a full
+    /// timezone database.
+    #[derive(Clone, Debug)]
+    struct TestDstTz;
+
+    impl TestDstTz {
+        // 2020-03-08 02:00 local jumps to 03:00 local (spring forward)
+        fn gap() -> (chrono::NaiveDateTime, chrono::NaiveDateTime) {
+            let d = chrono::NaiveDate::from_ymd(2020, 3, 8);
+            (d.and_hms(2, 0, 0), d.and_hms(3, 0, 0))
+        }
+
+        // 2020-11-01 01:00-02:00 local occurs twice (fall back)
+        fn fold() -> (chrono::NaiveDateTime, chrono::NaiveDateTime) {
+            let d = chrono::NaiveDate::from_ymd(2020, 11, 1);
+            (d.and_hms(1, 0, 0), d.and_hms(2, 0, 0))
+        }
+
+        fn standard_offset() -> chrono::FixedOffset {
+            chrono::FixedOffset::west(5 * 3600)
+        }
+
+        fn dst_offset() -> chrono::FixedOffset {
+            chrono::FixedOffset::west(4 * 3600)
+        }
+    }
+
+    impl chrono::TimeZone for TestDstTz {
+        type Offset = chrono::FixedOffset;
+
+        fn from_offset(_offset: &chrono::FixedOffset) -> TestDstTz {
+            TestDstTz
+        }
+
+        fn offset_from_local_date(
+            &self,
+            _local: &chrono::NaiveDate,
+        ) -> chrono::LocalResult<chrono::FixedOffset> {
+            chrono::LocalResult::Single(TestDstTz::standard_offset())
+        }
+
+        fn offset_from_local_datetime(
+            &self,
+            local: &chrono::NaiveDateTime,
+        ) -> chrono::LocalResult<chrono::FixedOffset> {
+            let (gap_start, gap_end) = TestDstTz::gap();
+            let (fold_start, fold_end) = TestDstTz::fold();
+
+            if *local >= gap_start && *local < gap_end {
+                chrono::LocalResult::None
+            } else if *local >= fold_start && *local < fold_end {
+                chrono::LocalResult::Ambiguous(TestDstTz::dst_offset(), TestDstTz::standard_offset())
+            } else if *local >= gap_end && *local < fold_start {
+                chrono::LocalResult::Single(TestDstTz::dst_offset())
+            } else {
+                chrono::LocalResult::Single(TestDstTz::standard_offset())
+            }
+        }
+
+        fn offset_from_utc_date(&self, _utc: &chrono::NaiveDate) -> chrono::FixedOffset {
+            TestDstTz::standard_offset()
+        }
+
+        fn offset_from_utc_datetime(&self, _utc: &chrono::NaiveDateTime) -> chrono::FixedOffset {
+            TestDstTz::standard_offset()
+        }
+    }
+
+    #[test]
+    fn naive_to_datetime_tz_resolves_fall_back_fold_to_earliest() {
+        // 01:30 local occurs twice on fall-back day; we should deterministically
+        // get the earlier (still-DST) occurrence rather than panicking.
+        let naive = chrono::NaiveDate::from_ymd(2020, 11, 1).and_hms(1, 30, 0);
+
+        let dt = naive_to_datetime_tz(ClockMode::Local, naive, &TestDstTz).unwrap();
+
+        assert_eq!(dt.offset(), &TestDstTz::dst_offset());
+    }
+
+    #[test]
+    fn naive_to_datetime_tz_errors_on_spring_forward_gap() {
+        // 02:30 local never happens on spring-forward day.
+        let naive = chrono::NaiveDate::from_ymd(2020, 3, 8).and_hms(2, 30, 0);
+
+        assert!(naive_to_datetime_tz(ClockMode::Local, naive, &TestDstTz).is_err());
+    }
+
+    #[test]
+    fn naive_to_datetime_tz_utc_mode_ignores_dst() {
+        // in UTC mode the reading is never reinterpreted as `tz`-local, so
+        // a "gap" instant is simply converted, not rejected.
+        let naive = chrono::NaiveDate::from_ymd(2020, 3, 8).and_hms(2, 30, 0);
+
+        assert!(naive_to_datetime_tz(ClockMode::Utc, naive, &TestDstTz).is_ok());
+    }
+}
+
+/// The time reference the hardware clock is kept in
+///
+/// Most Linux systems expect the RTC to be kept at UTC, but dual-boot
+/// machines (following the `hwclock --localtime` convention) often keep it
+/// at local time instead.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ClockMode {
+    /// The hardware clock reads and writes UTC
+    Utc,
+    /// The hardware clock reads and writes local time
+    Local,
+}
+
+impl Default for ClockMode {
+    fn default() -> ClockMode {
+        ClockMode::Utc
+    }
+}
+
+/// Interpret a raw clock reading as a timezone-aware `DateTime`
+///
+/// Pure helper behind [`HwClockDev::get_datetime_tz`], split out so the DST
+/// handling can be unit-tested without an actual hardware clock.
+///
+/// Twice a year, a `tz` observing DST makes some local readings ambiguous
+/// (fall back) or makes them not exist at all (spring forward). Since this
+/// is a real, recurring condition for an RTC kept in local time rather than
+/// a sign of broken hardware, an ambiguous reading deterministically
+/// resolves to the earlier of the two possible instants, while a reading
+/// that falls in the spring-forward gap is reported as an error rather than
+/// guessed at.
+fn naive_to_datetime_tz<Tz: chrono::TimeZone>(
+    mode: ClockMode,
+    naive: chrono::NaiveDateTime,
+    tz: &Tz,
+) -> io::Result<chrono::DateTime<Tz>> {
+    match mode {
+        // the raw reading is already `tz`-local time
+        ClockMode::Local => tz.from_local_datetime(&naive).earliest().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "RTC local time does not exist in the target timezone \
+                 (falls in a DST spring-forward gap)",
+            )
+        }),
+        // the raw reading is UTC; convert it to `tz`
+        ClockMode::Utc => Ok(chrono::Utc.from_utc_datetime(&naive).with_timezone(tz)),
+    }
 }
 
 /// Hardware clock
@@ -176,18 +623,42 @@ pub struct HwClockDev {
     // we store a full file instead of the raw fd, allowing us to print the
     // name of the clock using the derived debug impl
     clk: fs::File,
+    mode: ClockMode,
 }
 
 impl HwClockDev {
     /// Open clock
     ///
-    /// The device node will be held open until the `HwClockDev` is dropped
+    /// The device node will be held open until the `HwClockDev` is dropped.
+    /// Assumes the clock is kept at UTC; use
+    /// [`HwClockDev::open_with_mode`] if it is kept at local time instead.
     pub fn open<P: AsRef<path::Path>>(dev: P) -> io::Result<HwClockDev> {
+        HwClockDev::open_with_mode(dev, ClockMode::Utc)
+    }
+
+    /// Open clock, specifying whether it is kept at UTC or local time
+    ///
+    /// The device node will be held open until the `HwClockDev` is dropped.
+    pub fn open_with_mode<P: AsRef<path::Path>>(
+        dev: P,
+        mode: ClockMode,
+    ) -> io::Result<HwClockDev> {
         Ok(HwClockDev {
             clk: fs::File::open(dev)?,
+            mode,
         })
     }
 
+    /// Get the clock mode the device is assumed to be kept in
+    pub fn mode(&self) -> ClockMode {
+        self.mode
+    }
+
+    /// Set the clock mode the device is assumed to be kept in
+    pub fn set_mode(&mut self, mode: ClockMode) {
+        self.mode = mode;
+    }
+
     /// Get hardware clocks time
     pub fn get_time(&self) -> Result<RtcTime, nix::Error> {
         let mut time = RtcTime::default();
@@ -207,4 +678,384 @@ impl HwClockDev {
 
         Ok(())
     }
+
+    /// Get the currently configured alarm time
+    pub fn get_alarm(&self) -> Result<RtcTime, nix::Error> {
+        let mut time = RtcTime::default();
+
+        assert_eq!(0, unsafe {
+            ffi::rtc_alm_read(self.clk.as_raw_fd(), &mut time as *mut RtcTime)
+        }?);
+
+        Ok(time)
+    }
+
+    /// Set the alarm time
+    ///
+    /// Note that most hardware clocks only support the `tm_sec`, `tm_min` and
+    /// `tm_hour` fields of the alarm; use [`HwClockDev::set_wake_alarm`] if
+    /// the full date needs to be taken into account and the hardware supports
+    /// it.
+    pub fn set_alarm(&self, time: &RtcTime) -> Result<(), nix::Error> {
+        assert_eq!(0, unsafe {
+            ffi::rtc_alm_set(self.clk.as_raw_fd(), time as *const RtcTime)
+        }?);
+
+        Ok(())
+    }
+
+    /// Get the currently configured wakeup alarm
+    pub fn get_wake_alarm(&self) -> Result<RtcWkAlarm, nix::Error> {
+        let mut alarm = RtcWkAlarm::default();
+
+        assert_eq!(0, unsafe {
+            ffi::rtc_wkalm_rd(self.clk.as_raw_fd(), &mut alarm as *mut RtcWkAlarm)
+        }?);
+
+        Ok(alarm)
+    }
+
+    /// Set the wakeup alarm
+    pub fn set_wake_alarm(&self, alarm: &RtcWkAlarm) -> Result<(), nix::Error> {
+        assert_eq!(0, unsafe {
+            ffi::rtc_wkalm_set(self.clk.as_raw_fd(), alarm as *const RtcWkAlarm)
+        }?);
+
+        Ok(())
+    }
+
+    /// Enable or disable the alarm interrupt
+    pub fn alarm_interrupt_enable(&self, enable: bool) -> Result<(), nix::Error> {
+        assert_eq!(0, unsafe {
+            if enable {
+                ffi::rtc_aie_on(self.clk.as_raw_fd())
+            } else {
+                ffi::rtc_aie_off(self.clk.as_raw_fd())
+            }
+        }?);
+
+        Ok(())
+    }
+
+    /// Block until an RTC interrupt (alarm, update or periodic) occurs
+    ///
+    /// Performs a blocking `read()` on the underlying device, as described in
+    /// `Documentation/rtc.txt`. The alarm interrupt must be enabled via
+    /// [`HwClockDev::alarm_interrupt_enable`] beforehand, or this call will
+    /// block forever.
+    pub fn wait_for_alarm(&self) -> io::Result<AlarmEvent> {
+        let raw = self.read_irq_data()?;
+
+        Ok(AlarmEvent {
+            flags: (raw & 0xff) as u8,
+            count: raw >> 8,
+        })
+    }
+
+    /// Read a single `c_ulong` worth of interrupt data from the device
+    fn read_irq_data(&self) -> io::Result<c_ulong> {
+        let mut buf = [0u8; std::mem::size_of::<c_ulong>()];
+        (&self.clk).read_exact(&mut buf)?;
+
+        Ok(c_ulong::from_ne_bytes(buf))
+    }
+
+    /// Enable or disable the update interrupt
+    ///
+    /// When enabled, the RTC signals once per second, right after it rolls
+    /// over to the next second.
+    pub fn update_interrupt_enable(&self, enable: bool) -> Result<(), nix::Error> {
+        assert_eq!(0, unsafe {
+            if enable {
+                ffi::rtc_uie_on(self.clk.as_raw_fd())
+            } else {
+                ffi::rtc_uie_off(self.clk.as_raw_fd())
+            }
+        }?);
+
+        Ok(())
+    }
+
+    /// Block until the update interrupt fires, returning the interrupt count
+    ///
+    /// The update interrupt must be enabled via
+    /// [`HwClockDev::update_interrupt_enable`] beforehand, or this call will
+    /// block forever.
+    pub fn wait_for_update(&self) -> io::Result<u32> {
+        Ok((self.read_irq_data()? >> 8) as u32)
+    }
+
+    /// Enable or disable the periodic interrupt
+    pub fn periodic_interrupt_enable(&self, enable: bool) -> Result<(), nix::Error> {
+        assert_eq!(0, unsafe {
+            if enable {
+                ffi::rtc_pie_on(self.clk.as_raw_fd())
+            } else {
+                ffi::rtc_pie_off(self.clk.as_raw_fd())
+            }
+        }?);
+
+        Ok(())
+    }
+
+    /// Set the periodic interrupt rate, in Hz
+    pub fn set_periodic_rate(&self, hz: u32) -> Result<(), nix::Error> {
+        assert_eq!(0, unsafe {
+            ffi::rtc_irqp_set(self.clk.as_raw_fd(), hz as c_ulong)
+        }?);
+
+        Ok(())
+    }
+
+    /// Read the currently configured periodic interrupt rate, in Hz
+    pub fn periodic_rate(&self) -> Result<u32, nix::Error> {
+        let mut rate: c_ulong = 0;
+
+        assert_eq!(0, unsafe {
+            ffi::rtc_irqp_read(self.clk.as_raw_fd(), &mut rate as *mut c_ulong)
+        }?);
+
+        Ok(rate as u32)
+    }
+
+    /// Read the current time after latching it to the next second boundary
+    ///
+    /// Enables the update interrupt, blocks until it fires once, reads the
+    /// time and disables the interrupt again. This is useful when the caller
+    /// needs the time to be accurate to the second, rather than possibly
+    /// being read mid-second.
+    pub fn get_time_synced(&self) -> io::Result<RtcTime> {
+        self.update_interrupt_enable(true)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+
+        let result = self.wait_for_update().and_then(|_| {
+            self.get_time()
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+        });
+
+        // Don't let a failure to disable the interrupt mask the original
+        // error, if there was one.
+        let disable_result = self.update_interrupt_enable(false);
+        if result.is_ok() {
+            disable_result.map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        }
+
+        result
+    }
+
+    /// Get the hardware clock's time as a timezone-aware `DateTime`
+    ///
+    /// If the clock is in [`ClockMode::Local`] mode, the raw reading is
+    /// interpreted as `tz`-local time; otherwise it is interpreted as UTC and
+    /// converted to `tz`. Either way, the result carries the correct offset.
+    /// See [`naive_to_datetime_tz`] for how DST transitions are handled.
+    pub fn get_datetime_tz<Tz: chrono::TimeZone>(
+        &self,
+        tz: &Tz,
+    ) -> io::Result<chrono::DateTime<Tz>> {
+        let naive: chrono::NaiveDateTime = self
+            .get_time()
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+            .into();
+
+        naive_to_datetime_tz(self.mode, naive, tz)
+    }
+
+    /// Set the hardware clock's time from a timezone-aware `DateTime`
+    ///
+    /// If the clock is in [`ClockMode::Local`] mode, `dt` is converted to
+    /// `tz`-local time before being written; otherwise it is converted to
+    /// UTC, matching the RTC's expected reference.
+    pub fn set_datetime_tz<Tz: chrono::TimeZone>(
+        &self,
+        dt: &chrono::DateTime<Tz>,
+    ) -> Result<(), nix::Error> {
+        let naive = match self.mode {
+            ClockMode::Local => dt.naive_local(),
+            ClockMode::Utc => dt.naive_utc(),
+        };
+
+        self.set_time(&naive.into())
+    }
+
+    /// Read the epoch (base year) the hardware counts `tm_year` from
+    ///
+    /// This is distinct from [`YEAR_EPOCH`], which is the epoch this crate
+    /// assumes the kernel uses; some hardware allows it to be adjusted.
+    pub fn read_epoch(&self) -> Result<c_ulong, nix::Error> {
+        let mut epoch: c_ulong = 0;
+
+        assert_eq!(0, unsafe {
+            ffi::rtc_epoch_read(self.clk.as_raw_fd(), &mut epoch as *mut c_ulong)
+        }?);
+
+        Ok(epoch)
+    }
+
+    /// Set the epoch (base year) the hardware counts `tm_year` from
+    pub fn set_epoch(&self, epoch: c_ulong) -> Result<(), nix::Error> {
+        assert_eq!(0, unsafe {
+            ffi::rtc_epoch_set(self.clk.as_raw_fd(), epoch)
+        }?);
+
+        Ok(())
+    }
+
+    /// Read the oscillator's PLL correction info
+    pub fn get_pll(&self) -> Result<RtcPllInfo, nix::Error> {
+        let mut info = RtcPllInfo::default();
+
+        assert_eq!(0, unsafe {
+            ffi::rtc_pll_get(self.clk.as_raw_fd(), &mut info as *mut RtcPllInfo)
+        }?);
+
+        Ok(info)
+    }
+
+    /// Set the oscillator's PLL correction info
+    pub fn set_pll(&self, info: &RtcPllInfo) -> Result<(), nix::Error> {
+        assert_eq!(0, unsafe {
+            ffi::rtc_pll_set(self.clk.as_raw_fd(), info as *const RtcPllInfo)
+        }?);
+
+        Ok(())
+    }
+
+    /// Check whether the RTC's backup battery (e.g. a coin cell) is low
+    pub fn voltage_low(&self) -> Result<bool, nix::Error> {
+        let mut low: c_int = 0;
+
+        assert_eq!(0, unsafe {
+            ffi::rtc_vl_read(self.clk.as_raw_fd(), &mut low as *mut c_int)
+        }?);
+
+        Ok(low != 0)
+    }
+}
+
+/// Implementation of the `rtcc` ecosystem traits
+///
+/// Gated behind the `rtcc` feature, this lets a Linux `/dev/rtc*` device be
+/// used anywhere generic code expects an `rtcc::Rtcc`, e.g. alongside an
+/// on-chip RTC driven by a HAL such as `stm32f3xx-hal`.
+#[cfg(feature = "rtcc")]
+mod rtcc_impl {
+    use super::{HwClockDev, RtcTime};
+    use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+    use rtcc::{DateTimeAccess, Hours, Rtcc};
+
+    impl DateTimeAccess for HwClockDev {
+        type Error = nix::Error;
+
+        fn datetime(&mut self) -> Result<NaiveDateTime, Self::Error> {
+            HwClockDev::get_time(self).map(NaiveDateTime::from)
+        }
+
+        fn set_datetime(&mut self, datetime: &NaiveDateTime) -> Result<(), Self::Error> {
+            HwClockDev::set_time(self, &RtcTime::from(*datetime))
+        }
+    }
+
+    impl Rtcc for HwClockDev {
+        fn seconds(&mut self) -> Result<u8, Self::Error> {
+            HwClockDev::get_time(self).map(|t| t.second() as u8)
+        }
+
+        fn set_seconds(&mut self, seconds: u8) -> Result<(), Self::Error> {
+            let mut time = HwClockDev::get_time(self)?;
+            time.tm_sec = seconds as i32;
+            HwClockDev::set_time(self, &time)
+        }
+
+        fn minutes(&mut self) -> Result<u8, Self::Error> {
+            HwClockDev::get_time(self).map(|t| t.minute() as u8)
+        }
+
+        fn set_minutes(&mut self, minutes: u8) -> Result<(), Self::Error> {
+            let mut time = HwClockDev::get_time(self)?;
+            time.tm_min = minutes as i32;
+            HwClockDev::set_time(self, &time)
+        }
+
+        fn hours(&mut self) -> Result<Hours, Self::Error> {
+            HwClockDev::get_time(self).map(|t| Hours::H24(t.hour() as u8))
+        }
+
+        fn set_hours(&mut self, hours: Hours) -> Result<(), Self::Error> {
+            let mut time = HwClockDev::get_time(self)?;
+            time.tm_hour = match hours {
+                Hours::H24(h) => h as i32,
+                Hours::AM(h) => if h == 12 { 0 } else { h as i32 },
+                Hours::PM(h) => if h == 12 { 12 } else { h as i32 + 12 },
+            };
+            HwClockDev::set_time(self, &time)
+        }
+
+        fn time(&mut self) -> Result<NaiveTime, Self::Error> {
+            Ok(NaiveDateTime::from(HwClockDev::get_time(self)?).time())
+        }
+
+        fn set_time(&mut self, time: &NaiveTime) -> Result<(), Self::Error> {
+            let mut rtc_time = HwClockDev::get_time(self)?;
+            rtc_time.tm_sec = time.second() as i32;
+            rtc_time.tm_min = time.minute() as i32;
+            rtc_time.tm_hour = time.hour() as i32;
+            HwClockDev::set_time(self, &rtc_time)
+        }
+
+        fn weekday(&mut self) -> Result<u8, Self::Error> {
+            // the `rtcc` ecosystem convention is the day number starting
+            // from Sunday = 1, not `chrono`'s Monday = 1
+            HwClockDev::get_time(self)
+                .map(|t| NaiveDateTime::from(t).weekday().number_from_sunday() as u8)
+        }
+
+        fn set_weekday(&mut self, _weekday: u8) -> Result<(), Self::Error> {
+            // `tm_wday` is ignored by the kernel when setting the time; it is
+            // always derived from the date fields instead.
+            Ok(())
+        }
+
+        fn day(&mut self) -> Result<u8, Self::Error> {
+            HwClockDev::get_time(self).map(|t| t.tm_mday as u8)
+        }
+
+        fn set_day(&mut self, day: u8) -> Result<(), Self::Error> {
+            let mut time = HwClockDev::get_time(self)?;
+            time.tm_mday = day as i32;
+            HwClockDev::set_time(self, &time)
+        }
+
+        fn month(&mut self) -> Result<u8, Self::Error> {
+            HwClockDev::get_time(self).map(|t| t.tm_mon as u8 + 1)
+        }
+
+        fn set_month(&mut self, month: u8) -> Result<(), Self::Error> {
+            let mut time = HwClockDev::get_time(self)?;
+            time.tm_mon = month as i32 - 1;
+            HwClockDev::set_time(self, &time)
+        }
+
+        fn year(&mut self) -> Result<u16, Self::Error> {
+            HwClockDev::get_time(self).map(|t| (t.tm_year + super::YEAR_EPOCH) as u16)
+        }
+
+        fn set_year(&mut self, year: u16) -> Result<(), Self::Error> {
+            let mut time = HwClockDev::get_time(self)?;
+            time.tm_year = year as i32 - super::YEAR_EPOCH;
+            HwClockDev::set_time(self, &time)
+        }
+
+        fn date(&mut self) -> Result<NaiveDate, Self::Error> {
+            Ok(NaiveDateTime::from(HwClockDev::get_time(self)?).date())
+        }
+
+        fn set_date(&mut self, date: &NaiveDate) -> Result<(), Self::Error> {
+            let mut time = HwClockDev::get_time(self)?;
+            time.tm_mday = date.day() as i32;
+            time.tm_mon = date.month0() as i32;
+            time.tm_year = date.year() - super::YEAR_EPOCH;
+            HwClockDev::set_time(self, &time)
+        }
+    }
 }